@@ -4,6 +4,7 @@ use std::{
     fs::{File, OpenOptions},
     io, io::Write,
     os::unix::fs::MetadataExt,
+    os::unix::io::AsRawFd,
     path, path::PathBuf,
     process,
     thread::sleep};
@@ -12,9 +13,9 @@ use clap::{arg, command, Parser, Subcommand};
 use chrono::Local;
 use expanduser::expanduser;
 use file_guard::Lock;
+use io_uring::{opcode, squeue, types, IoUring, Probe};
 
 static FORMAT_NOW: &'static str = "%H:%M:%S";
-static LINE_SIZE: usize = 13usize;
 
 #[derive(Subcommand)]
 enum Action {
@@ -26,7 +27,46 @@ enum Action {
         sleep: u32,
         #[clap(long, short='p', required = false, default_value_t = false)]
         /// Disable inotify and employ polling instead
-        use_polling: bool
+        use_polling: bool,
+        #[clap(long, required = false, default_value_t = false)]
+        /// Follow the file through an io_uring submission queue instead of polling/inotify
+        io_uring: bool,
+        #[clap(long, required = false)]
+        /// Directory to drop timing-anomaly "clip" files in; enables the clip-capture subsystem
+        clip_dir: Option<PathBuf>,
+        #[clap(long, default_value_t = 15000u64)]
+        /// Inter-line gap, in milliseconds, that counts as a timing anomaly. Gaps are only ever
+        /// observed at --sleep poll-tick granularity, so keep this meaningfully larger than
+        /// --sleep (in milliseconds) or every poll cycle of a normally-behaving writer will look
+        /// like a stall
+        gap_ms: u64,
+        #[clap(long, default_value_t = 20usize)]
+        /// Number of preceding lines kept in the clip-capture ring buffer
+        clip_lines: usize,
+        #[clap(long, default_value_t = 50u32)]
+        /// Maximum number of clip files retained under --clip-dir
+        max_clips: u32,
+        #[clap(long, required = false, default_value_t = false)]
+        /// Follow with an adaptive slow/fast poll cadence instead of a fixed --sleep interval
+        adaptive: bool,
+        #[clap(long, default_value_t = 10u32)]
+        /// Coarse poll interval, in seconds, used while the file is quiet
+        slow_interval: u32,
+        #[clap(long, default_value_t = 100u32)]
+        /// Fine poll interval, in milliseconds, used right after growth is detected
+        fast_interval: u32,
+        #[clap(long, default_value_t = 5u32)]
+        /// Number of consecutive quiet ticks at --fast-interval before decaying back to --slow-interval
+        idle_decay: u32,
+        #[clap(long, required = false, conflicts_with_all = ["from_end", "lines"])]
+        /// Start following from this absolute byte offset instead of the last line
+        from_offset: Option<u64>,
+        #[clap(long, required = false, conflicts_with_all = ["from_offset", "lines"])]
+        /// Start following this many bytes back from the end of the file
+        from_end: Option<u64>,
+        #[clap(long, required = false, conflicts_with_all = ["from_offset", "from_end"])]
+        /// Replay this many trailing lines before following, computed via an in-process seek/tell scan
+        lines: Option<usize>
     },
     /// [alias: w] Append current time to the file at specified intervals
     #[clap(alias = "w")]
@@ -36,16 +76,42 @@ enum Action {
         interval: u32,
         #[clap(long, short='l', required = false, default_value_t = false)]
         /// Claim the lock when writing to the file
-        use_locking: bool
+        use_locking: bool,
+        #[clap(long, required = false, default_value_t = false)]
+        /// Submit writes through an io_uring queue instead of blocking on sleep/write/fsync
+        io_uring: bool,
+        #[clap(long, default_value = "%t")]
+        /// Directive string for the appended line: %s file size, %t time, %T epoch ms, %n line counter, %% literal
+        format: String,
+        #[clap(long, value_enum, default_value_t = SyncMode::Data)]
+        /// Durability per write: no sync, sync_data only, or a full sync_all
+        sync: SyncMode,
+        #[clap(long, required = false)]
+        /// Reserve this many bytes ahead of the current end of file via fallocate, growing in
+        /// further chunks of the same size as the offset approaches the reserved end
+        prealloc: Option<u64>
     }
 }
 
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+enum SyncMode {
+    /// Never sync; rely on the OS to flush dirty pages on its own schedule
+    None,
+    /// `sync_data` after every write (today's default behaviour)
+    Data,
+    /// `sync_all`, flushing file content and metadata after every write
+    Full
+}
+
 fn action_fmt(action: &Action, f: &mut Formatter) -> fmt::Result {
     match action {
-        Action::Read{ sleep: ref i, use_polling: ref polling} =>
-            write!(f, "Read with {:?} s sleep interval with {}", *i, if *polling { "polling" } else { "inotify subsystem" }),
-        Action::Write{ interval: ref i, use_locking: ref locking} =>
-            write!(f, "Write at {:?} ms with{} locking", *i, if *locking { "" } else { "out" })
+        Action::Read{ sleep: ref i, use_polling: ref polling, io_uring: ref uring, clip_dir: ref clip, .. } =>
+            write!(f, "Read with {:?} s sleep interval with {}{}", *i,
+                   if *uring { "io_uring" } else if *polling { "polling" } else { "inotify subsystem" },
+                   clip.as_ref().map_or(String::new(), |d| format!(" and clip capture to {:?}", d))),
+        Action::Write{ interval: ref i, use_locking: ref locking, io_uring: ref uring, format: ref fmt, .. } =>
+            write!(f, "Write at {:?} ms with{} locking{} using format {:?}", *i, if *locking { "" } else { "out" },
+                   if *uring { " via io_uring" } else { "" }, fmt)
     }
 }
 
@@ -75,8 +141,426 @@ impl Cli {
     }
 }
 
+/// Probe whether the running kernel can set up an io_uring instance at all;
+/// used to decide whether `--io-uring` can be honoured or must fall back.
+fn io_uring_supported() -> bool {
+    IoUring::new(4).is_ok()
+}
+
+/// Probe whether the running kernel's io_uring backend actually implements every opcode in
+/// `codes`, not just whether `io_uring_setup` itself succeeds — a ring can be set up fine on a
+/// kernel that still returns `EOPNOTSUPP`/`EINVAL` for an individual op (e.g. `STATX`). Used to
+/// decide whether `--io-uring` can be honoured or must fall back to the synchronous/polling path.
+fn io_uring_ops_supported(codes: &[u8]) -> bool {
+    let Ok(ring) = IoUring::new(4) else { return false; };
+    let mut probe = Probe::new();
+    if ring.submitter().register_probe(&mut probe).is_err() {
+        return false;
+    }
+    codes.iter().all(|&code| probe.is_supported(code))
+}
+
+/// Follow `file_path` by polling size via `IORING_OP_STATX` and draining growth
+/// with buffered `IORING_OP_READ` completions, instead of shelling out to `tail`.
+fn tail_uring(file_path: PathBuf, sleep: &u32) -> io::Result<()> {
+    let mut file = OpenOptions::new().read(true).open(&file_path)?;
+    let fd = types::Fd(file.as_raw_fd());
+    let mut ring = IoUring::new(8)?;
+    let size = get_size(&file)? as u64;
+    let mut offset = offset_of_last_lines(&mut file, size, 1)?;
+    let mut buf = vec![0u8; 64 * 1024];
+    let empty_path = std::ffi::CString::new("").unwrap();
+
+    loop {
+        let mut statx_buf = types::statx::default();
+        let statx_e = opcode::Statx::new(fd, empty_path.as_ptr(), &mut statx_buf)
+            .flags(libc::AT_EMPTY_PATH)
+            .mask(libc::STATX_SIZE)
+            .build()
+            .user_data(0);
+        unsafe { ring.submission().push(&statx_e).map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?; }
+        ring.submit_and_wait(1)?;
+        let Some(cqe) = ring.completion().next() else {
+            return Err(io::Error::new(io::ErrorKind::Other, "io_uring statx produced no completion."));
+        };
+        if cqe.result() < 0 {
+            return Err(io::Error::from_raw_os_error(-cqe.result()));
+        }
+
+        let new_size = statx_buf.stx_size;
+        if new_size > offset {
+            let read_e = opcode::Read::new(fd, buf.as_mut_ptr(), buf.len() as _)
+                .offset(offset)
+                .build()
+                .user_data(1);
+            unsafe { ring.submission().push(&read_e).map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?; }
+            ring.submit_and_wait(1)?;
+            let Some(cqe) = ring.completion().next() else {
+                return Err(io::Error::new(io::ErrorKind::Other, "io_uring read produced no completion."));
+            };
+            let read = cqe.result();
+            if read < 0 {
+                return Err(io::Error::from_raw_os_error(-read));
+            }
+            if read > 0 {
+                let read = read as usize;
+                print!("{}", String::from_utf8_lossy(&buf[..read]));
+                io::stdout().flush()?;
+                offset += read as u64;
+            }
+        } else if new_size < offset {
+            offset = new_size;
+        }
+
+        sleep_secs(*sleep);
+    }
+}
+
 #[inline(always)]
-fn tail(file_path: PathBuf, sleep: &u32, use_polling: &bool) {
+fn sleep_secs(seconds: u32) {
+    sleep(core::time::Duration::from_secs(seconds as u64));
+}
+
+/// Read whatever bytes were appended to `file` since `offset`, carrying over any trailing
+/// partial line in `carry`, and return the complete lines newly available. Detects truncation
+/// (e.g. log rotation) by resetting the cursor to zero when the file has shrunk.
+fn drain_new_lines(file: &mut File, offset: &mut u64, carry: &mut String) -> io::Result<Vec<String>> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let size = get_size(file)? as u64;
+    if size < *offset {
+        *offset = 0;
+        carry.clear();
+    }
+    if size <= *offset {
+        return Ok(Vec::new());
+    }
+
+    let mut delta = vec![0u8; (size - *offset) as usize];
+    file.seek(SeekFrom::Start(*offset))?;
+    file.read_exact(&mut delta)?;
+    *offset = size;
+    carry.push_str(&String::from_utf8_lossy(&delta));
+
+    let mut lines = Vec::new();
+    let mut consumed = 0usize;
+    while let Some(nl) = carry[consumed..].find('\n') {
+        lines.push(carry[consumed..consumed + nl].to_string());
+        consumed += nl + 1;
+    }
+    carry.drain(..consumed);
+    Ok(lines)
+}
+
+/// Follow `file_path` with a state machine over its observed growth: stay at the coarse
+/// `slow_interval` while quiet, drop to the fine `fast_interval` as soon as growth is seen,
+/// and decay back to `slow_interval` after `idle_decay` consecutive quiet ticks at the fast rate.
+fn tail_adaptive(file_path: PathBuf, slow_interval: &u32, fast_interval: &u32, idle_decay: &u32) -> io::Result<()> {
+    let mut file = File::open(&file_path)?;
+    let size = get_size(&file)? as u64;
+    let mut offset = offset_of_last_lines(&mut file, size, 1)?;
+    let mut carry = String::new();
+    let slow = core::time::Duration::from_secs(*slow_interval as u64);
+    let fast = core::time::Duration::from_millis(*fast_interval as u64);
+    let mut current = slow;
+    let mut idle_ticks = 0u32;
+
+    loop {
+        let lines = drain_new_lines(&mut file, &mut offset, &mut carry)?;
+        if lines.is_empty() {
+            if current == fast {
+                idle_ticks += 1;
+                if idle_ticks >= *idle_decay {
+                    current = slow;
+                    idle_ticks = 0;
+                }
+            }
+        } else {
+            for line in lines {
+                println!("{}", line);
+            }
+            io::stdout().flush()?;
+            current = fast;
+            idle_ticks = 0;
+        }
+        sleep(current);
+    }
+}
+
+/// Scan `file` backwards in fixed-size chunks to find the byte offset at which the last `k`
+/// complete lines begin, without reading the whole file into memory.
+fn offset_of_last_lines(file: &mut File, size: u64, k: usize) -> io::Result<u64> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    if k == 0 || size == 0 {
+        return Ok(size);
+    }
+    const CHUNK: u64 = 8192;
+    let mut pos = size;
+    let mut newlines = 0usize;
+    let mut buf = vec![0u8; CHUNK as usize];
+
+    while pos > 0 {
+        let read_len = CHUNK.min(pos);
+        pos -= read_len;
+        file.seek(SeekFrom::Start(pos))?;
+        file.read_exact(&mut buf[..read_len as usize])?;
+        for i in (0..read_len as usize).rev() {
+            if buf[i] != b'\n' {
+                continue;
+            }
+            let abs = pos + i as u64;
+            if abs == size - 1 {
+                continue; // the file's own trailing newline terminates the last line, not a separator
+            }
+            newlines += 1;
+            if newlines == k {
+                return Ok(abs + 1);
+            }
+        }
+    }
+    Ok(0)
+}
+
+/// Resolve the byte offset `--from-offset`/`--from-end`/`--lines` ask the follower to start at,
+/// defaulting to the current end of the file (today's "last line, then follow" behaviour).
+fn resolve_start_offset(
+    file: &mut File, size: u64, from_offset: &Option<u64>, from_end: &Option<u64>, lines: &Option<usize>
+) -> io::Result<u64> {
+    if let Some(off) = from_offset {
+        return Ok((*off).min(size));
+    }
+    if let Some(back) = from_end {
+        return Ok(size.saturating_sub(*back));
+    }
+    if let Some(k) = lines {
+        return offset_of_last_lines(file, size, *k);
+    }
+    offset_of_last_lines(file, size, 1)
+}
+
+/// Follow `file_path` from an explicit byte offset using `Seek`/`read_at`-style positional reads,
+/// maintaining an internal cursor (`tell`) across iterations instead of relying on `uu_tail`'s
+/// descriptor-following semantics.
+fn tail_positional(file_path: PathBuf, sleep_interval: &u32, start_offset: u64) -> io::Result<()> {
+    let mut file = File::open(&file_path)?;
+    let mut offset = start_offset;
+    let mut carry = String::new();
+
+    loop {
+        let lines = drain_new_lines(&mut file, &mut offset, &mut carry)?;
+        if !lines.is_empty() {
+            for line in lines {
+                println!("{}", line);
+            }
+            io::stdout().flush()?;
+        }
+        sleep_secs(*sleep_interval);
+    }
+}
+
+static CLIP_CONTEXT_LINES: usize = 5;
+
+#[derive(Clone)]
+struct ClipLine {
+    text: String,
+    observed_at: chrono::DateTime<Local>
+}
+
+/// Render the ring-buffer snapshot plus the following-context lines into a single clip file,
+/// then evict the oldest clip(s) once `max_clips` is exceeded.
+fn write_clip(
+    clip_dir: &PathBuf,
+    before: &std::collections::VecDeque<ClipLine>,
+    after: &[ClipLine],
+    queue: &mut std::collections::VecDeque<PathBuf>,
+    max_clips: &u32
+) -> io::Result<()> {
+    let mut contents = String::new();
+    for line in before.iter().chain(after.iter()) {
+        contents.push_str(&format!("[{}] {}\n", line.observed_at.format("%H:%M:%S%.3f"), line.text));
+    }
+    let path = clip_dir.join(format!("clip-{}.txt", Local::now().format("%Y%m%dT%H%M%S%.3f")));
+    std::fs::write(&path, contents)?;
+    queue.push_back(path);
+    while queue.len() > *max_clips as usize {
+        if let Some(oldest) = queue.pop_front() {
+            let _ = std::fs::remove_file(oldest);
+        }
+    }
+    Ok(())
+}
+
+/// Follow `file_path` line-by-line, keeping a bounded ring buffer of recently observed lines.
+/// Whenever the gap since the previous line exceeds `gap_ms`, dump the buffer together with
+/// a few following lines into a timestamped clip file, maintaining `--clip-dir` as its own
+/// bounded queue so only the most recent `max_clips` clips survive.
+///
+/// Gaps are measured between the timestamps at which lines are drained from a poll batch, not
+/// their real arrival time, so the gap between the last line of one batch and the first line of
+/// the next is ~one `sleep_interval`; keep `gap_ms` meaningfully larger than that poll interval
+/// or every tick of a normally-behaving writer will look like a stall.
+fn tail_with_clips(
+    file_path: PathBuf,
+    sleep_interval: &u32,
+    gap_ms: &u64,
+    clip_dir: &PathBuf,
+    clip_lines: &usize,
+    max_clips: &u32
+) -> io::Result<()> {
+    use std::collections::VecDeque;
+
+    std::fs::create_dir_all(clip_dir)?;
+    let mut clip_queue: VecDeque<PathBuf> = std::fs::read_dir(clip_dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("clip-")))
+        .collect();
+    clip_queue.make_contiguous().sort();
+    while clip_queue.len() > *max_clips as usize {
+        if let Some(oldest) = clip_queue.pop_front() {
+            let _ = std::fs::remove_file(oldest);
+        }
+    }
+
+    let mut file = File::open(&file_path)?;
+    let size = get_size(&file)? as u64;
+    let mut offset = offset_of_last_lines(&mut file, size, 1)?;
+    let mut ring: VecDeque<ClipLine> = VecDeque::with_capacity(*clip_lines);
+    let mut last_line_at: Option<chrono::DateTime<Local>> = None;
+    let mut pending: Option<(VecDeque<ClipLine>, Vec<ClipLine>, chrono::DateTime<Local>)> = None;
+    let mut carry = String::new();
+
+    loop {
+        let lines = drain_new_lines(&mut file, &mut offset, &mut carry)?;
+        if !lines.is_empty() {
+            for line in lines {
+                println!("{}", line);
+
+                let now = Local::now();
+                if let Some(prev) = last_line_at {
+                    let gap = (now - prev).num_milliseconds();
+                    if gap > *gap_ms as i64 && pending.is_none() {
+                        pending = Some((ring.clone(), Vec::with_capacity(CLIP_CONTEXT_LINES), now));
+                    }
+                }
+                last_line_at = Some(now);
+
+                let clip_line = ClipLine { text: line, observed_at: now };
+                if let Some((before, after, _)) = pending.as_mut() {
+                    after.push(clip_line.clone());
+                    if after.len() >= CLIP_CONTEXT_LINES {
+                        write_clip(clip_dir, &*before, after.as_slice(), &mut clip_queue, max_clips)?;
+                        pending = None;
+                    }
+                }
+
+                if *clip_lines > 0 {
+                    if ring.len() == *clip_lines {
+                        ring.pop_front();
+                    }
+                    ring.push_back(clip_line);
+                }
+            }
+            io::stdout().flush()?;
+        }
+
+        // A stall right after the anomalous line is the worst case this feature exists to
+        // capture, so don't hold `pending` hostage waiting for `CLIP_CONTEXT_LINES` more lines
+        // that may never arrive: flush whatever context landed once the gap itself times out.
+        if let Some((before, after, opened_at)) = pending.as_ref() {
+            if (Local::now() - *opened_at).num_milliseconds() > *gap_ms as i64 {
+                write_clip(clip_dir, before, after.as_slice(), &mut clip_queue, max_clips)?;
+                pending = None;
+            }
+        }
+
+        sleep_secs(*sleep_interval);
+    }
+}
+
+#[inline(always)]
+fn tail(
+    file_path: PathBuf, sleep: &u32, use_polling: &bool, io_uring: &bool,
+    clip_dir: &Option<PathBuf>, gap_ms: &u64, clip_lines: &usize, max_clips: &u32,
+    adaptive: &bool, slow_interval: &u32, fast_interval: &u32, idle_decay: &u32,
+    from_offset: &Option<u64>, from_end: &Option<u64>, lines: &Option<usize>
+) {
+    let positional = from_offset.is_some() || from_end.is_some() || lines.is_some();
+    if positional {
+        if clip_dir.is_some() {
+            println!("WARN: --clip-dir is ignored when --from-offset/--from-end/--lines selects positional follow.");
+        }
+        if *adaptive {
+            println!("WARN: --adaptive is ignored when --from-offset/--from-end/--lines selects positional follow.");
+        }
+        if *io_uring {
+            println!("WARN: --io-uring is ignored when --from-offset/--from-end/--lines selects positional follow.");
+        }
+    } else if clip_dir.is_some() {
+        if *adaptive {
+            println!("WARN: --adaptive is ignored when --clip-dir selects clip-capture follow.");
+        }
+        if *io_uring {
+            println!("WARN: --io-uring is ignored when --clip-dir selects clip-capture follow.");
+        }
+    } else if *adaptive && *io_uring {
+        println!("WARN: --io-uring is ignored when --adaptive selects adaptive-cadence follow.");
+    }
+
+    if from_offset.is_some() || from_end.is_some() || lines.is_some() {
+        let mut file = match File::open(&file_path) {
+            Ok(file) => file,
+            Err(err) => { eprintln!("WARN: positional follow failed ({}); exiting.", err); process::exit(1); }
+        };
+        let size = get_size(&file).unwrap_or(0) as u64;
+        let start = resolve_start_offset(&mut file, size, from_offset, from_end, lines)
+            .expect("Cannot resolve the starting offset.");
+        println!("Following {:?} file descriptor positionally from byte offset {}.", file_path, start);
+        if let Err(err) = tail_positional(file_path, sleep, start) {
+            eprintln!("WARN: positional follow failed ({}); exiting.", err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(dir) = clip_dir {
+        let sleep_ms = *sleep as u64 * 1000;
+        if *gap_ms <= sleep_ms {
+            println!("WARN: --gap-ms ({}) is not meaningfully larger than the --sleep poll interval ({} ms); \
+                      gaps are only ever observed at poll-tick granularity, so expect spurious clips on every \
+                      poll cycle of a normally-behaving writer.", *gap_ms, sleep_ms);
+        }
+        println!("Following {:?} file descriptor with clip capture to {:?} (gap threshold {} ms).", file_path, dir, *gap_ms);
+        if let Err(err) = tail_with_clips(file_path, sleep, gap_ms, dir, clip_lines, max_clips) {
+            eprintln!("WARN: clip-capture follow failed ({}); exiting.", err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if *adaptive {
+        println!("Following {:?} file descriptor with adaptive cadence ({} s slow / {} ms fast, decaying after {} quiet ticks).",
+                 file_path, *slow_interval, *fast_interval, *idle_decay);
+        if let Err(err) = tail_adaptive(file_path, slow_interval, fast_interval, idle_decay) {
+            eprintln!("WARN: adaptive follow failed ({}); exiting.", err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if *io_uring {
+        if io_uring_supported() && io_uring_ops_supported(&[opcode::Statx::CODE, opcode::Read::CODE]) {
+            println!("Following {:?} file descriptor using io_uring.", file_path);
+            if let Err(err) = tail_uring(file_path, sleep) {
+                eprintln!("WARN: io_uring follow failed ({}); exiting.", err);
+                process::exit(1);
+            }
+            return;
+        }
+        println!("WARN: io_uring (or one of the statx/read ops it needs) is unavailable on this kernel; falling back to {}.",
+                 if *use_polling { "polling" } else { "inotify subsystem" });
+    }
+
     println!("Following {:?} file descriptor using {}", file_path, if *use_polling { "polling." } else { "inotify subsystem." });
     let mut args = vec![
         OsString::from("tail"),
@@ -91,13 +575,46 @@ fn tail(file_path: PathBuf, sleep: &u32, use_polling: &bool) {
     uu_tail::uumain(args.into_iter());
 }
 
-fn write_line(mut file: &File) -> io::Result<()> {
-    let now = Local::now();
-    let now_str = format!("{}.{:0>3}", now.format(FORMAT_NOW), now.timestamp_subsec_millis());
-    writeln!(file, "{}", now_str)?;
+/// Render `format` against the known `file_size` and `line_no`, expanding `%s`/`%t`/`%T`/`%n`/`%%`
+/// directives. Unknown directives and a dangling trailing `%` are rejected with an error that
+/// points at the offending slice instead of panicking.
+fn render_line(format: &str, file_size: usize, line_no: u64) -> io::Result<String> {
+    let mut out = String::with_capacity(format.len());
+    let mut chars = format.char_indices();
+    while let Some((idx, ch)) = chars.next() {
+        if ch != '%' {
+            out.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some((_, 's')) => out.push_str(&file_size.to_string()),
+            Some((_, 't')) => {
+                let now = Local::now();
+                out.push_str(&format!("{}.{:0>3}", now.format(FORMAT_NOW), now.timestamp_subsec_millis()));
+            }
+            Some((_, 'T')) => out.push_str(&Local::now().timestamp_millis().to_string()),
+            Some((_, 'n')) => out.push_str(&line_no.to_string()),
+            Some((_, '%')) => out.push('%'),
+            Some((_, other)) => return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("invalid directive '%{}' at byte {} in format {:?}", other, idx, format))),
+            None => return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("dangling '%' at byte {} in format {:?}", idx, format)))
+        }
+    }
+    Ok(out)
+}
+
+fn write_rendered(mut file: &File, rendered: &str, sync: SyncMode) -> io::Result<()> {
+    writeln!(file, "{}", rendered)?;
     file.flush()?;
-    println!("{}", now_str);
-    file.sync_data()?;
+    println!("{}", rendered);
+    match sync {
+        SyncMode::None => {}
+        SyncMode::Data => file.sync_data()?,
+        SyncMode::Full => file.sync_all()?
+    }
     Ok(())
 }
 
@@ -108,23 +625,73 @@ fn get_size(file: &File) -> io::Result<usize> {
     )
 }
 
+/// Reserve `len` bytes on disk starting at `offset` without changing the file's apparent size
+/// (`FALLOC_FL_KEEP_SIZE`), so append-mode writes still land right after the real content.
+/// Returns `false` on any failure (unsupported filesystem, wrong `fallocate` mode, ...) so
+/// callers can fall back to the filesystem's normal on-demand growth.
+fn try_preallocate(file: &File, offset: u64, len: u64) -> bool {
+    let ret = unsafe {
+        libc::fallocate(file.as_raw_fd(), libc::FALLOC_FL_KEEP_SIZE, offset as libc::off_t, len as libc::off_t)
+    };
+    ret == 0
+}
+
+/// Grow the reservation by another `chunk` bytes once the file has grown to within one quarter
+/// of a chunk of the previously reserved end. `allocated_to` is set to `u64::MAX` the first time
+/// `fallocate` fails, so further ticks stop retrying and just let the filesystem grow on demand.
+fn maintain_preallocation(file: &File, allocated_to: &mut u64, chunk: u64, file_size: u64) {
+    if *allocated_to == u64::MAX || file_size + chunk / 4 < *allocated_to {
+        return;
+    }
+    if try_preallocate(file, *allocated_to, chunk) {
+        *allocated_to += chunk;
+    } else {
+        *allocated_to = u64::MAX;
+    }
+}
+
+fn init_preallocation(file: &File, prealloc: Option<u64>) -> u64 {
+    let Some(chunk) = prealloc else { return u64::MAX; };
+    let file_size = get_size(file).unwrap_or(0) as u64;
+    if try_preallocate(file, file_size, chunk) { file_size + chunk } else { u64::MAX }
+}
+
 //noinspection SpellCheckingInspection
-fn write_nolock(file: File, duration: &core::time::Duration) ->! {
+fn write_nolock(file: File, duration: &core::time::Duration, format: &str, sync: SyncMode, prealloc: Option<u64>) ->! {
+    render_line(format, 0, 0).expect("Invalid format directive.");
+    let needs_size = format.contains("%s") || prealloc.is_some();
+    let mut line_no = 0u64;
+    let mut allocated_to = init_preallocation(&file, prealloc);
     loop {
         sleep(*duration);
-        write_line(&file).expect("Cannot append a line to the file.");
+        line_no += 1;
+        let file_size = if needs_size { get_size(&file).expect("Cannot get the file size.") } else { 0 };
+        if let Some(chunk) = prealloc {
+            maintain_preallocation(&file, &mut allocated_to, chunk, file_size as u64);
+        }
+        let rendered = render_line(format, file_size, line_no).expect("Invalid format directive.");
+        write_rendered(&file, &rendered, sync).expect("Cannot append a line to the file.");
     }
 }
 
-fn write_lock(mut file: File, duration: &core::time::Duration) ->! {
+fn write_lock(mut file: File, duration: &core::time::Duration, format: &str, sync: SyncMode, prealloc: Option<u64>) ->! {
+    render_line(format, 0, 0).expect("Invalid format directive.");
+    let mut line_no = 0u64;
+    let mut allocated_to = init_preallocation(&file, prealloc);
     loop {
         sleep(*duration);
+        line_no += 1;
         let file_size = get_size(&file).expect("Cannot get the file size.");
+        if let Some(chunk) = prealloc {
+            maintain_preallocation(&file, &mut allocated_to, chunk, file_size as u64);
+        }
+        let rendered = render_line(format, file_size, line_no).expect("Invalid format directive.");
+        let line_size = rendered.len() + 1; // +1 for the trailing newline written by write_rendered
         let lock_result = file_guard::try_lock(
             &mut file,
             Lock::Exclusive,
             usize::MIN,
-            file_size + LINE_SIZE);
+            file_size + line_size);
         let Ok(mut lock) = lock_result else {
             println!("WARN: Cannot lock the file; append skipped.");
             continue;
@@ -133,22 +700,96 @@ fn write_lock(mut file: File, duration: &core::time::Duration) ->! {
             println!("WARN: The file size has changed; append skipped.");
         }
         else {
-            write_line(lock.deref_mut()).expect("Cannot append a line to the file.");
+            write_rendered(lock.deref_mut(), &rendered, sync).expect("Cannot append a line to the file.");
         }
         drop(lock);
     }
 }
 
-fn write(file_path: PathBuf, interval: &u32, locking: &bool) ->! {
-    println!("Writing to: {:?} every {} milliseconds with{} locking.", file_path, *interval, if *locking { "" } else { "out" });
-    let duration = core::time::Duration::from_millis(*interval as u64);
-    let file = OpenOptions::new()
+/// Submit a timed `WRITE -> FSYNC -> TIMEOUT` chain per tick via io_uring (the `FSYNC` op is
+/// skipped for `SyncMode::None` and omits the datasync flag for `SyncMode::Full`), so the pacing
+/// interval is enforced by the kernel timer rather than a blocking `sleep`.
+fn write_uring(file: File, duration: &core::time::Duration, format: &str, sync: SyncMode, prealloc: Option<u64>) -> io::Result<()> {
+    render_line(format, 0, 0)?;
+    let needs_size = format.contains("%s") || prealloc.is_some();
+    let fd = types::Fd(file.as_raw_fd());
+    let mut ring = IoUring::new(8)?;
+    let ts = types::Timespec::new()
+        .sec(duration.as_secs())
+        .nsec(duration.subsec_nanos());
+    let mut line_no = 0u64;
+    let mut allocated_to = init_preallocation(&file, prealloc);
+
+    loop {
+        line_no += 1;
+        let file_size = if needs_size { get_size(&file)? } else { 0 };
+        if let Some(chunk) = prealloc {
+            maintain_preallocation(&file, &mut allocated_to, chunk, file_size as u64);
+        }
+        let rendered = render_line(format, file_size, line_no)?;
+        let line = format!("{}\n", rendered).into_bytes();
+
+        let write_e = opcode::Write::new(fd, line.as_ptr(), line.len() as _)
+            .build()
+            .flags(squeue::Flags::IO_LINK)
+            .user_data(0);
+        let timeout_e = opcode::Timeout::new(&ts)
+            .build()
+            .user_data(2);
+
+        unsafe {
+            let mut sq = ring.submission();
+            sq.push(&write_e).map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+            if sync != SyncMode::None {
+                let mut fsync_e = opcode::Fsync::new(fd);
+                if sync == SyncMode::Data {
+                    fsync_e = fsync_e.flags(types::FsyncFlags::DATASYNC);
+                }
+                let fsync_e = fsync_e.build().flags(squeue::Flags::IO_LINK).user_data(1);
+                sq.push(&fsync_e).map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+            }
+            sq.push(&timeout_e).map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+        }
+        let expected = if sync == SyncMode::None { 2 } else { 3 };
+        ring.submit_and_wait(expected)?;
+        for cqe in ring.completion() {
+            if cqe.user_data() != 2 && cqe.result() < 0 {
+                return Err(io::Error::from_raw_os_error(-cqe.result()));
+            }
+        }
+        println!("{}", rendered);
+    }
+}
+
+fn open_append(file_path: &PathBuf) -> File {
+    OpenOptions::new()
         .append(true)
         .create(true)
-        .open(file_path).expect("Cannot open the file.");
+        .open(file_path).expect("Cannot open the file.")
+}
+
+fn write(file_path: PathBuf, interval: &u32, locking: &bool, io_uring: &bool, format: &str, sync: SyncMode, prealloc: Option<u64>) ->! {
+    let duration = core::time::Duration::from_millis(*interval as u64);
+
+    if *io_uring {
+        let mut needed_ops = vec![opcode::Write::CODE, opcode::Timeout::CODE];
+        if sync != SyncMode::None {
+            needed_ops.push(opcode::Fsync::CODE);
+        }
+        if io_uring_supported() && io_uring_ops_supported(&needed_ops) {
+            if *locking {
+                println!("WARN: --use-locking has no effect with --io-uring; the io_uring write path never takes the file lock.");
+            }
+            println!("Writing to: {:?} every {} milliseconds via io_uring.", file_path, *interval);
+            write_uring(open_append(&file_path), &duration, format, sync, prealloc).expect("io_uring write loop failed.");
+        }
+        println!("WARN: io_uring (or one of the write/fsync/timeout ops it needs) is unavailable on this kernel; falling back to the synchronous path.");
+    }
+
+    println!("Writing to: {:?} every {} milliseconds with{} locking.", file_path, *interval, if *locking { "" } else { "out" });
     match *locking {
-        true => write_lock(file, &duration),
-        false => write_nolock(file, &duration)
+        true => write_lock(open_append(&file_path), &duration, format, sync, prealloc),
+        false => write_nolock(open_append(&file_path), &duration, format, sync, prealloc)
     }
 }
 
@@ -159,13 +800,110 @@ pub fn main() {
         process::exit(0);
     }).expect("Cannot set SIGINT handler.");
 
-    match args.command.unwrap_or(Action::Read{ sleep: 10u32, use_polling: false }) {
-        Action::Read { sleep: ref interval, use_polling: ref polling } if file.is_file() =>
-            { tail(file, interval, polling); }
-        Action::Write { interval: ref sleep, use_locking: ref locking } =>
-            { write(file, sleep, locking); }
+    match args.command.unwrap_or(Action::Read{
+        sleep: 10u32, use_polling: false, io_uring: false, clip_dir: None, gap_ms: 15000u64, clip_lines: 20usize, max_clips: 50u32,
+        adaptive: false, slow_interval: 10u32, fast_interval: 100u32, idle_decay: 5u32,
+        from_offset: None, from_end: None, lines: None
+    }) {
+        Action::Read {
+            sleep: ref interval, use_polling: ref polling, io_uring: ref uring,
+            clip_dir: ref clip, gap_ms: ref gap, clip_lines: ref clip_lines, max_clips: ref max,
+            adaptive: ref adaptive, slow_interval: ref slow, fast_interval: ref fast, idle_decay: ref decay,
+            from_offset: ref from_offset, from_end: ref from_end, lines: ref lines
+        } if file.is_file() =>
+            { tail(file, interval, polling, uring, clip, gap, clip_lines, max, adaptive, slow, fast, decay, from_offset, from_end, lines); }
+        Action::Write { interval: ref sleep, use_locking: ref locking, io_uring: ref uring, format: ref fmt, sync, prealloc } =>
+            { write(file, sleep, locking, uring, fmt, sync, prealloc); }
         _ => { println!("'{}' is not a file!", file.display()) }
     };
     
     process::exit(1)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_line_substitutes_known_directives() {
+        assert_eq!(render_line("%s bytes, line %n%%", 42, 7).unwrap(), "42 bytes, line 7%");
+    }
+
+    #[test]
+    fn render_line_passes_through_plain_text() {
+        assert_eq!(render_line("no directives here", 0, 0).unwrap(), "no directives here");
+    }
+
+    #[test]
+    fn render_line_rejects_unknown_directive() {
+        let err = render_line("%q", 0, 0).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("invalid directive"));
+    }
+
+    #[test]
+    fn render_line_rejects_dangling_percent() {
+        let err = render_line("trailing %", 0, 0).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert!(err.to_string().contains("dangling"));
+    }
+
+    fn temp_file_with(name: &str, contents: &str) -> File {
+        let path = std::env::temp_dir().join(format!("watchers-test-{}-{}", process::id(), name));
+        std::fs::write(&path, contents).expect("Cannot write the temp file.");
+        OpenOptions::new().read(true).write(true).open(&path).expect("Cannot open the temp file.")
+    }
+
+    #[test]
+    fn offset_of_last_lines_with_k_zero_returns_size() {
+        let mut file = temp_file_with("k-zero", "one\ntwo\nthree\n");
+        assert_eq!(offset_of_last_lines(&mut file, 14, 0).unwrap(), 14);
+    }
+
+    #[test]
+    fn offset_of_last_lines_on_empty_file_returns_zero() {
+        let mut file = temp_file_with("empty", "");
+        assert_eq!(offset_of_last_lines(&mut file, 0, 3).unwrap(), 0);
+    }
+
+    #[test]
+    fn offset_of_last_lines_ignores_the_trailing_newline_as_a_separator() {
+        let contents = "one\ntwo\nthree\n";
+        let mut file = temp_file_with("trailing-newline", contents);
+        let offset = offset_of_last_lines(&mut file, contents.len() as u64, 1).unwrap();
+        assert_eq!(&contents[offset as usize..], "three\n");
+    }
+
+    #[test]
+    fn offset_of_last_lines_with_no_trailing_newline() {
+        let contents = "one\ntwo\nthree";
+        let mut file = temp_file_with("no-trailing-newline", contents);
+        let offset = offset_of_last_lines(&mut file, contents.len() as u64, 2).unwrap();
+        assert_eq!(&contents[offset as usize..], "two\nthree");
+    }
+
+    #[test]
+    fn offset_of_last_lines_with_a_blank_last_line() {
+        let contents = "one\ntwo\n\n";
+        let mut file = temp_file_with("blank-last-line", contents);
+        let offset = offset_of_last_lines(&mut file, contents.len() as u64, 1).unwrap();
+        assert_eq!(&contents[offset as usize..], "\n");
+    }
+
+    #[test]
+    fn offset_of_last_lines_crossing_a_chunk_boundary() {
+        // 1000 fixed-width lines of 10 bytes each make a 10000-byte file; the last 900 lines
+        // (9000 bytes) reach past the first 8192-byte backward read into the second one.
+        let line = "x".repeat(9);
+        let mut contents = String::new();
+        for _ in 0..1000 {
+            contents.push_str(&line);
+            contents.push('\n');
+        }
+        let mut file = temp_file_with("chunk-boundary", &contents);
+        let offset = offset_of_last_lines(&mut file, contents.len() as u64, 900).unwrap();
+        let tail = &contents[offset as usize..];
+        assert_eq!(offset, 1000);
+        assert_eq!(tail, format!("{}\n", line).repeat(900));
+    }
+}